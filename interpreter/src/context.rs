@@ -0,0 +1,128 @@
+use crate::functions::CelFunction;
+use crate::objects::CelType;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Evaluation context threading variable bindings and registered functions
+/// through expression resolution.
+///
+/// Cloning a [`Context`] (as the comprehension macros in [`crate::functions`]
+/// do to scope an intermediate identifier) produces a [`Context::Child`]:
+/// the parent's bindings are preserved behind an [`Rc`] and consulted on
+/// lookup miss, while new variables/functions added to the clone are never
+/// visible to the parent.
+pub enum Context {
+    Root {
+        variables: HashMap<String, CelType>,
+        functions: HashMap<String, CelFunction>,
+    },
+    Child {
+        parent: Rc<Context>,
+        variables: HashMap<String, CelType>,
+        functions: HashMap<String, CelFunction>,
+    },
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context::Root {
+            variables: HashMap::new(),
+            functions: HashMap::new(),
+        }
+    }
+}
+
+impl Clone for Context {
+    fn clone(&self) -> Self {
+        Context::Child {
+            parent: Rc::new(self.snapshot()),
+            variables: HashMap::new(),
+            functions: HashMap::new(),
+        }
+    }
+}
+
+impl Context {
+    /// Deep-copies this context's own data without wrapping it in a new
+    /// [`Context::Child`] layer, unlike [`Clone::clone`].
+    fn snapshot(&self) -> Context {
+        match self {
+            Context::Root {
+                variables,
+                functions,
+            } => Context::Root {
+                variables: variables.clone(),
+                functions: functions.clone(),
+            },
+            Context::Child {
+                parent,
+                variables,
+                functions,
+            } => Context::Child {
+                parent: parent.clone(),
+                variables: variables.clone(),
+                functions: functions.clone(),
+            },
+        }
+    }
+
+    /// Binds `name` to `value` in the current scope.
+    pub fn add_variable(&mut self, name: &str, value: impl Into<CelType>) {
+        let variables = match self {
+            Context::Root { variables, .. } => variables,
+            Context::Child { variables, .. } => variables,
+        };
+        variables.insert(name.to_string(), value.into());
+    }
+
+    /// Resolves `name`, checking the current scope before falling back to
+    /// the parent scope (if any).
+    pub fn resolve_variable(&self, name: &str) -> Option<&CelType> {
+        match self {
+            Context::Root { variables, .. } => variables.get(name),
+            Context::Child {
+                variables, parent, ..
+            } => variables.get(name).or_else(|| parent.resolve_variable(name)),
+        }
+    }
+
+    /// Registers a custom function under `name`, overriding any previously
+    /// registered function (or built-in, once resolved via
+    /// [`Self::resolve_function`]) of the same name.
+    ///
+    /// `function` receives the same `(target, args, ctx)` signature as the
+    /// built-ins in [`crate::functions`], so it can be invoked as either a
+    /// function or a method (e.g. `isAdmin(user)` or `user.isAdmin()`).
+    pub fn add_function<F>(&mut self, name: &str, function: F)
+    where
+        F: Fn(
+                Option<&CelType>,
+                &[cel_parser::Expression],
+                &Context,
+            ) -> Result<CelType, crate::ExecutionError>
+            + 'static,
+    {
+        let functions = match self {
+            Context::Root { functions, .. } => functions,
+            Context::Child { functions, .. } => functions,
+        };
+        functions.insert(name.to_string(), Rc::new(function));
+    }
+
+    /// Looks up a user-registered function by name, checking the current
+    /// scope before falling back to the parent scope (if any). Returns
+    /// `None` if no function with this name has been registered, in which
+    /// case the resolver falls back to the built-ins in [`crate::functions`]
+    /// (see [`crate::functions::call_function`]).
+    pub fn resolve_function(&self, name: &str) -> Option<CelFunction> {
+        match self {
+            Context::Root { functions, .. } => functions.get(name).cloned(),
+            Context::Child {
+                functions, parent, ..
+            } => functions
+                .get(name)
+                .cloned()
+                .or_else(|| parent.resolve_function(name)),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::objects::CelType;
+use std::rc::Rc;
+
+/// Errors that can occur while resolving a CEL expression or invoking a
+/// built-in or user-registered function.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionError {
+    /// A variable, property, or key could not be resolved.
+    NoSuchKey(Rc<String>),
+    /// A function or macro was called with the wrong number of arguments.
+    InvalidArgumentCount { expected: usize, actual: usize },
+    /// A function that can only be called as a function was called as a
+    /// method.
+    NotSupportedAsMethod { name: String, target: CelType },
+    /// A method-style function or comprehension macro requires a target and
+    /// none was given.
+    MissingArgumentOrTarget,
+    /// A [`CelType`] could not be used as a map key.
+    UnsupportedKeyType(CelType),
+    /// A function-specific runtime error, e.g. an invalid regex pattern,
+    /// an out-of-range index, or an unsupported conversion.
+    FunctionError { function: String, message: String },
+    /// No built-in or registered function exists with this name.
+    UndefinedFunction(String),
+}
+
+impl ExecutionError {
+    pub fn no_such_key(key: Rc<String>) -> Self {
+        ExecutionError::NoSuchKey(key)
+    }
+
+    pub fn invalid_argument_count(expected: usize, actual: usize) -> Self {
+        ExecutionError::InvalidArgumentCount { expected, actual }
+    }
+
+    pub fn not_supported_as_method(name: &str, target: CelType) -> Self {
+        ExecutionError::NotSupportedAsMethod {
+            name: name.to_string(),
+            target,
+        }
+    }
+
+    pub fn missing_argument_or_target() -> Self {
+        ExecutionError::MissingArgumentOrTarget
+    }
+
+    pub fn function_error(function: &str, message: &str) -> Self {
+        ExecutionError::FunctionError {
+            function: function.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Returned by the resolver when a call or method expression names a
+    /// function that is neither registered on the [`crate::context::Context`]
+    /// nor one of the built-ins in [`crate::functions`].
+    pub fn undefined_function(name: &str) -> Self {
+        ExecutionError::UndefinedFunction(name.to_string())
+    }
+}
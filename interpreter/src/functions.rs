@@ -2,9 +2,84 @@ use crate::context::Context;
 use crate::objects::CelType;
 use crate::ExecutionError;
 use cel_parser::Expression;
+use chrono::{DateTime, Datelike, FixedOffset, Timelike, Utc};
+use regex::Regex;
 use std::convert::TryInto;
 use std::rc::Rc;
 
+/// The signature shared by every built-in function in this module, such as
+/// [`size`] and [`contains`].
+///
+/// Exposed so that [`Context::add_function`] can register host functions
+/// with the same shape, letting embedders supply their own domain logic
+/// (e.g. `isAdmin(user)`) that is resolved the same way as the built-ins
+/// defined here, overriding or supplementing the standard set.
+pub type CelFunction =
+    Rc<dyn Fn(Option<&CelType>, &[Expression], &Context) -> Result<CelType, ExecutionError>>;
+
+/// Resolves a call or method expression named `name` to its implementation
+/// and invokes it. Consults `ctx`'s function registry first (see
+/// [`Context::add_function`]), letting a host-registered function override a
+/// built-in of the same name, then falls back to [`call_builtin`]. Returns
+/// [`ExecutionError::undefined_function`] if `name` matches neither.
+pub fn call_function(
+    name: &str,
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    match ctx.resolve_function(name) {
+        Some(function) => function(target, args, ctx),
+        None => call_builtin(name, target, args, ctx),
+    }
+}
+
+/// Dispatches to one of this module's built-in functions by name, as called
+/// from [`call_function`] once the context's registry has been checked.
+/// Returns [`ExecutionError::undefined_function`] if `name` doesn't match
+/// any built-in.
+fn call_builtin(
+    name: &str,
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    match name {
+        "size" => size(target, args, ctx),
+        "contains" => contains(target, args, ctx),
+        "has" => has(target, args, ctx),
+        "map" => map(target, args, ctx),
+        "filter" => filter(target, args, ctx),
+        "all" => all(target, args, ctx),
+        "exists" => exists(target, args, ctx),
+        "exists_one" => exists_one(target, args, ctx),
+        "matches" => matches(target, args, ctx),
+        "startsWith" => starts_with(target, args, ctx),
+        "endsWith" => ends_with(target, args, ctx),
+        "substring" => substring(target, args, ctx),
+        "split" => split(target, args, ctx),
+        "trim" => trim(target, args, ctx),
+        "indexOf" => index_of(target, args, ctx),
+        "timestamp" => timestamp(target, args, ctx),
+        "duration" => duration(target, args, ctx),
+        "getFullYear" => get_full_year(target, args, ctx),
+        "getMonth" => get_month(target, args, ctx),
+        "getDayOfMonth" => get_day_of_month(target, args, ctx),
+        "getHours" => get_hours(target, args, ctx),
+        "getMinutes" => get_minutes(target, args, ctx),
+        "getSeconds" => get_seconds(target, args, ctx),
+        "getMilliseconds" => get_milliseconds(target, args, ctx),
+        "int" => int(target, args, ctx),
+        "uint" => uint(target, args, ctx),
+        "double" => double(target, args, ctx),
+        "string" => string(target, args, ctx),
+        "bytes" => bytes(target, args, ctx),
+        "bool" => bool(target, args, ctx),
+        "type" => type_of(target, args, ctx),
+        _ => Err(ExecutionError::undefined_function(name)),
+    }
+}
+
 /// Calculates the size of either the target, or the provided args depending on how
 /// the function is called. If called as a method, the target will be used. If called
 /// as a function, the first argument will be used.
@@ -44,7 +119,10 @@ pub fn size(
             &format!("cannot determine size of {:?}", value),
         ))?,
     };
-    CelType::Int(size as i32).into()
+    let size: i64 = size
+        .try_into()
+        .map_err(|_| ExecutionError::function_error("size", "size exceeds maximum supported integer"))?;
+    CelType::Int(size).into()
 }
 
 /// Returns true if the target contains the provided argument. The actual behavior
@@ -54,7 +132,7 @@ pub fn size(
 /// * [`CelType::List`] - Returns true if the list contains the provided value.
 /// * [`CelType::Map`] - Returns true if the map contains the provided key.
 /// * [`CelType::String`] - Returns true if the string contains the provided substring.
-/// * [`CelType::Bytes`] - Returns true if the bytes contain the provided byte.
+/// * [`CelType::Bytes`] - Returns true if the bytes contain the provided byte sequence.
 ///
 /// # Example
 ///
@@ -76,6 +154,7 @@ pub fn size(
 /// ## Bytes
 /// ```cel
 /// b"abc".contains(b"c") == true
+/// b"abc".contains(b"bc") == true
 /// ```
 pub fn contains(
     target: Option<&CelType>,
@@ -101,18 +180,8 @@ pub fn contains(
         }
         CelType::Bytes(b) => {
             if let CelType::Bytes(arg) = arg {
-                // When search raw bytes, we can only search for a single byte right now.
-                let length = arg.len();
-                if length > 1 {
-                    return Err(ExecutionError::function_error(
-                        "contains",
-                        &format!("expected 1 byte, found {}", length),
-                    ))?;
-                }
-                arg.as_slice()
-                    .first()
-                    .map(|byte| b.contains(byte))
-                    .unwrap_or(false)
+                // The empty needle is trivially contained in any haystack.
+                arg.is_empty() || b.windows(arg.len()).any(|window| window == arg.as_slice())
             } else {
                 false
             }
@@ -122,143 +191,1330 @@ pub fn contains(
     .into())
 }
 
-/// Returns true if the provided argument can be resolved. This function is
-/// useful for checking if a property exists on a type before attempting to
-/// resolve it. Resolving a property that does not exist will result in a
-/// [`ExecutionError::NoSuchKey`] error.
+/// Returns true if the target string matches the provided regular
+/// expression. The pattern is compiled with the [`regex`] crate, and an
+/// invalid pattern results in a [`ExecutionError::function_error`].
 ///
-/// Operates similar to the `has` macro describe in the Go CEL implementation
-/// spec: https://github.com/google/cel-spec/blob/master/doc/langdef.md#macros.
+/// # Examples
+/// ```cel
+/// "hello world".matches("^hello") == true
+/// ```
+pub fn matches(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    let s = as_string("matches", target)?;
+    let pattern = as_string("matches", &resolve_arg("matches", args, 0, ctx)?)?;
+    let re = Regex::new(&pattern).map_err(|e| {
+        ExecutionError::function_error("matches", &format!("invalid regex '{}': {}", pattern, e))
+    })?;
+    Ok(CelType::Bool(re.is_match(&s)).into())
+}
+
+/// Returns true if the target string starts with the provided prefix.
 ///
 /// # Examples
 /// ```cel
-/// has(foo.bar.baz)
+/// "hello world".startsWith("hello") == true
 /// ```
-pub fn has(
+pub fn starts_with(
     target: Option<&CelType>,
     args: &[Expression],
     ctx: &Context,
 ) -> Result<CelType, ExecutionError> {
-    if target.is_some() {
-        return Err(ExecutionError::not_supported_as_method(
-            "has",
-            target.cloned().unwrap(),
-        ));
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    let s = as_string("startsWith", target)?;
+    let prefix = as_string("startsWith", &resolve_arg("startsWith", args, 0, ctx)?)?;
+    Ok(CelType::Bool(s.starts_with(prefix.as_str())).into())
+}
+
+/// Returns true if the target string ends with the provided suffix.
+///
+/// # Examples
+/// ```cel
+/// "hello world".endsWith("world") == true
+/// ```
+pub fn ends_with(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    let s = as_string("endsWith", target)?;
+    let suffix = as_string("endsWith", &resolve_arg("endsWith", args, 0, ctx)?)?;
+    Ok(CelType::Bool(s.ends_with(suffix.as_str())).into())
+}
+
+/// Returns the substring of the target string between `start` (inclusive)
+/// and `end` (exclusive), both measured in bytes. Errors if either bound
+/// falls outside the target string or doesn't land on a character boundary.
+///
+/// # Examples
+/// ```cel
+/// "hello world".substring(0, 5) == "hello"
+/// ```
+pub fn substring(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    let s = as_string("substring", target)?;
+    let start = as_int("substring", &resolve_arg("substring", args, 0, ctx)?)?;
+    let end = as_int("substring", &resolve_arg("substring", args, 1, ctx)?)?;
+    let out_of_range = || {
+        ExecutionError::function_error(
+            "substring",
+            &format!("index out of range: start={}, end={}, len={}", start, end, s.len()),
+        )
+    };
+    let start: usize = start.try_into().map_err(|_| out_of_range())?;
+    let end: usize = end.try_into().map_err(|_| out_of_range())?;
+    if end < start || end > s.len() {
+        return Err(out_of_range());
     }
+    s.get(start..end)
+        .map(|slice| CelType::String(Rc::new(slice.to_string())).into())
+        .ok_or_else(|| {
+            ExecutionError::function_error("substring", "start and end must lie on a character boundary")
+        })
+}
+
+/// Splits the target string on the provided separator, returning a list of
+/// the resulting substrings.
+///
+/// # Examples
+/// ```cel
+/// "a,b,c".split(",") == ["a", "b", "c"]
+/// ```
+pub fn split(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    let s = as_string("split", target)?;
+    let sep = as_string("split", &resolve_arg("split", args, 0, ctx)?)?;
+    let parts = s
+        .split(sep.as_str())
+        .map(|part| CelType::String(Rc::new(part.to_string())))
+        .collect();
+    Ok(CelType::List(Rc::new(parts)).into())
+}
+
+/// Returns the target string with leading and trailing whitespace removed.
+///
+/// # Examples
+/// ```cel
+/// "  hello  ".trim() == "hello"
+/// ```
+pub fn trim(
+    target: Option<&CelType>,
+    _args: &[Expression],
+    _ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    let s = as_string("trim", target)?;
+    Ok(CelType::String(Rc::new(s.trim().to_string())).into())
+}
+
+/// Returns the byte index of the first occurrence of the provided substring
+/// in the target string, or `-1` if it is not found.
+///
+/// # Examples
+/// ```cel
+/// "hello world".indexOf("world") == 6
+/// ```
+pub fn index_of(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    let s = as_string("indexOf", target)?;
+    let needle = as_string("indexOf", &resolve_arg("indexOf", args, 0, ctx)?)?;
+    let index = s.find(needle.as_str()).map(|i| i as i64).unwrap_or(-1);
+    Ok(CelType::Int(index).into())
+}
+
+/// Resolves the argument at `index`, returning an [`ExecutionError`] naming
+/// `fn_name` if it is missing.
+fn resolve_arg(
+    fn_name: &str,
+    args: &[Expression],
+    index: usize,
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
     let arg = args
-        .get(0)
-        .ok_or(ExecutionError::invalid_argument_count(1, 0))?;
+        .get(index)
+        .ok_or(ExecutionError::invalid_argument_count(index + 1, args.len()))?;
+    CelType::resolve(arg, ctx)
+}
 
-    // We determine if a type has a property by attempting to resolve it.
-    // If we get a NoSuchKey error, then we know the property does not exist
-    match CelType::resolve(arg, ctx) {
-        Ok(_) => CelType::Bool(true),
-        Err(err) => match err {
-            ExecutionError::NoSuchKey(_) => CelType::Bool(false),
-            _ => return Err(err),
-        },
+/// Unwraps a [`CelType::String`], returning an [`ExecutionError::function_error`]
+/// naming `fn_name` for any other variant.
+fn as_string(fn_name: &str, value: &CelType) -> Result<Rc<String>, ExecutionError> {
+    match value {
+        CelType::String(s) => Ok(s.clone()),
+        _ => Err(ExecutionError::function_error(
+            fn_name,
+            &format!("expected a string, found {:?}", value),
+        )),
+    }
+}
+
+/// Unwraps a [`CelType::Int`], returning an [`ExecutionError::function_error`]
+/// naming `fn_name` for any other variant.
+fn as_int(fn_name: &str, value: &CelType) -> Result<i64, ExecutionError> {
+    match value {
+        CelType::Int(i) => Ok(*i),
+        _ => Err(ExecutionError::function_error(
+            fn_name,
+            &format!("expected an int, found {:?}", value),
+        )),
     }
-    .into()
 }
 
-/// Maps the provided list to a new list by applying an expression to each
-/// input item. This function is intended to be used like the CEL-go `map`
-/// macro: https://github.com/google/cel-spec/blob/master/doc/langdef.md#macros
+/// Parses an RFC 3339 string into a [`CelType::Timestamp`]. Always called as
+/// a function, never as a method.
 ///
-/// The macro allows the user to assign each item in the list to an arbitrary
-/// identifier, and then use that identifier in the expression. In order to
-/// make this work here, we clone the context which creates a [`Context::Child`]
-/// context with the new variable. The child context has it's own variable
-/// space, so you can think about this is a sort of scoping mechanism.
+/// # Examples
+/// ```cel
+/// timestamp("2023-01-01T00:00:00Z").getFullYear() == 2023
+/// ```
+pub fn timestamp(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    if target.is_some() {
+        return Err(ExecutionError::not_supported_as_method(
+            "timestamp",
+            target.cloned().unwrap(),
+        ));
+    }
+    let s = as_string("timestamp", &resolve_arg("timestamp", args, 0, ctx)?)?;
+    let dt = DateTime::parse_from_rfc3339(&s).map_err(|e| {
+        ExecutionError::function_error("timestamp", &format!("invalid timestamp '{}': {}", s, e))
+    })?;
+    Ok(CelType::Timestamp(dt.with_timezone(&Utc)).into())
+}
+
+/// Parses a duration string such as `"300s"` or `"1h30m"` into a
+/// [`CelType::Duration`]. Always called as a function, never as a method.
 ///
 /// # Examples
 /// ```cel
-/// [1, 2, 3].map(x, x * 2) == [2, 4, 6]
+/// duration("1h30m").getMinutes() == 30
 /// ```
-pub fn map(
+pub fn duration(
     target: Option<&CelType>,
     args: &[Expression],
     ctx: &Context,
 ) -> Result<CelType, ExecutionError> {
-    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
-    if args.len() != 2 {
-        return Err(ExecutionError::invalid_argument_count(2, args.len()));
+    if target.is_some() {
+        return Err(ExecutionError::not_supported_as_method(
+            "duration",
+            target.cloned().unwrap(),
+        ));
+    }
+    let s = as_string("duration", &resolve_arg("duration", args, 0, ctx)?)?;
+    let d = parse_duration(&s)
+        .ok_or_else(|| ExecutionError::function_error("duration", &format!("invalid duration '{}'", s)))?;
+    Ok(CelType::Duration(d).into())
+}
+
+/// Parses duration strings like `"300s"` or `"1h30m"`: a sequence of
+/// decimal numbers each immediately followed by a `h`, `m`, or `s` unit.
+fn parse_duration(s: &str) -> Option<chrono::Duration> {
+    let mut total = chrono::Duration::zero();
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() || c == '.' || c == '-' {
+            number.push(c);
+            continue;
+        }
+        let value: f64 = number.parse().ok()?;
+        number.clear();
+        let millis = match c {
+            'h' => value * 3_600_000.0,
+            'm' => value * 60_000.0,
+            's' => value * 1_000.0,
+            _ => return None,
+        };
+        total = total + chrono::Duration::milliseconds(millis as i64);
+    }
+    if !number.is_empty() {
+        return None;
     }
-    let ident = get_ident(&args[0])?;
-    if let CelType::List(items) = target {
-        let mut values = Vec::with_capacity(items.len());
+    Some(total)
+}
 
-        // Initialize a new context where we'll store our intermediate identifier
-        // for each item that we're mapping over. This ensures that we don't overwrite
-        // any identifiers in the parent scope just because we use the same name in
-        // the mapping expression.
-        let mut ctx = ctx.clone();
-        for item in items.iter() {
-            ctx.add_variable(&**ident, item.clone());
-            let value = CelType::resolve(&args[1], &ctx)?;
-            values.push(value);
+/// Resolves the timezone argument at `args[0]`, if present, applying it to
+/// `dt`. Defaults to UTC when no timezone argument is given.
+fn apply_timezone(
+    fn_name: &str,
+    dt: DateTime<Utc>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<DateTime<FixedOffset>, ExecutionError> {
+    match args.get(0) {
+        Some(arg) => {
+            let tz = as_string(fn_name, &CelType::resolve(arg, ctx)?)?;
+            let offset = parse_fixed_offset(&tz).ok_or_else(|| {
+                ExecutionError::function_error(fn_name, &format!("invalid timezone '{}'", tz))
+            })?;
+            Ok(dt.with_timezone(&offset))
         }
+        None => Ok(dt.with_timezone(&FixedOffset::east_opt(0).unwrap())),
+    }
+}
 
-        Ok(CelType::List(Rc::new(values)))
-    } else {
-        Err(ExecutionError::function_error(
-            "map",
-            "map can only be called on a list",
-        ))
+/// Parses a fixed UTC offset of the form `"Z"`, `"+HH:MM"`, or `"-HH:MM"`.
+fn parse_fixed_offset(tz: &str) -> Option<FixedOffset> {
+    if tz == "Z" || tz == "UTC" {
+        return FixedOffset::east_opt(0);
     }
+    let (sign, rest) = match tz.as_bytes().first()? {
+        b'+' => (1, &tz[1..]),
+        b'-' => (-1, &tz[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = rest.split_once(':')?;
+    // Parse as i64 and keep the seconds computation in i64 so an
+    // implausible offset like "+1000000:00" can't overflow i32 before
+    // FixedOffset::east_opt gets a chance to reject it as out of range.
+    let hours: i64 = hours.parse().ok()?;
+    let minutes: i64 = minutes.parse().ok()?;
+    let seconds = sign as i64 * (hours * 3600 + minutes * 60);
+    let seconds: i32 = seconds.try_into().ok()?;
+    FixedOffset::east_opt(seconds)
 }
 
-fn get_ident(expr: &Expression) -> Result<Rc<String>, ExecutionError> {
-    match expr {
-        Expression::Ident(ident) => Ok(ident.clone()),
+/// Unwraps a [`CelType::Timestamp`], returning an [`ExecutionError::function_error`]
+/// naming `fn_name` for any other variant.
+fn as_timestamp(fn_name: &str, value: &CelType) -> Result<DateTime<Utc>, ExecutionError> {
+    match value {
+        CelType::Timestamp(dt) => Ok(*dt),
         _ => Err(ExecutionError::function_error(
-            "map",
-            "first argument must be an identifier",
+            fn_name,
+            &format!("expected a timestamp, found {:?}", value),
         )),
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::context::Context;
-    use crate::testing::test_script;
-    use std::collections::HashMap;
+/// Returns the four-digit year of the target timestamp, optionally in the
+/// provided timezone.
+///
+/// # Examples
+/// ```cel
+/// timestamp("2023-06-15T00:00:00Z").getFullYear() == 2023
+/// ```
+pub fn get_full_year(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    let dt = as_timestamp("getFullYear", target)?;
+    let dt = apply_timezone("getFullYear", dt, args, ctx)?;
+    Ok(CelType::Int(dt.year() as i64).into())
+}
 
-    #[test]
-    fn test_size() {
-        let tests = vec![
-            ("size of list", "size([1, 2, 3]) == 3"),
-            ("size of map", "size({'a': 1, 'b': 2, 'c': 3}) == 3"),
-            ("size of string", "size('foo') == 3"),
-            ("size of bytes", "size(b'foo') == 3"),
-        ];
+/// Returns the zero-based month (`0`-`11`) of the target timestamp,
+/// optionally in the provided timezone.
+///
+/// # Examples
+/// ```cel
+/// timestamp("2023-06-15T00:00:00Z").getMonth() == 5
+/// ```
+pub fn get_month(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    let dt = as_timestamp("getMonth", target)?;
+    let dt = apply_timezone("getMonth", dt, args, ctx)?;
+    Ok(CelType::Int(dt.month0() as i64).into())
+}
 
-        for (name, script) in tests {
-            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+/// Returns the zero-based day of the month of the target timestamp,
+/// optionally in the provided timezone.
+///
+/// # Examples
+/// ```cel
+/// timestamp("2023-06-15T00:00:00Z").getDayOfMonth() == 14
+/// ```
+pub fn get_day_of_month(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    let dt = as_timestamp("getDayOfMonth", target)?;
+    let dt = apply_timezone("getDayOfMonth", dt, args, ctx)?;
+    Ok(CelType::Int(dt.day0() as i64).into())
+}
+
+/// Returns the hour (`0`-`23`) component of the target timestamp or
+/// duration, optionally in the provided timezone (timestamps only).
+pub fn get_hours(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    match target {
+        CelType::Duration(d) => Ok(CelType::Int(d.num_hours() % 24).into()),
+        _ => {
+            let dt = apply_timezone("getHours", as_timestamp("getHours", target)?, args, ctx)?;
+            Ok(CelType::Int(dt.hour() as i64).into())
         }
     }
+}
 
-    #[test]
-    fn test_has() {
-        let tests = vec![
-            ("map has", "has(foo.bar) == true"),
-            ("map has", "has(foo.bar) == true"),
-            ("map not has", "has(foo.baz) == false"),
-            ("map deep not has", "has(foo.baz.bar) == false"),
-        ];
+/// Returns the minute (`0`-`59`) component of the target timestamp or
+/// duration, optionally in the provided timezone (timestamps only).
+pub fn get_minutes(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    match target {
+        CelType::Duration(d) => Ok(CelType::Int(d.num_minutes() % 60).into()),
+        _ => {
+            let dt = apply_timezone("getMinutes", as_timestamp("getMinutes", target)?, args, ctx)?;
+            Ok(CelType::Int(dt.minute() as i64).into())
+        }
+    }
+}
 
-        for (name, script) in tests {
-            let mut ctx = Context::default();
-            ctx.add_variable("foo", HashMap::from([("bar", 1)]));
-            assert_eq!(test_script(script, Some(ctx)), Ok(true.into()), "{}", name);
+/// Returns the second (`0`-`59`) component of the target timestamp or
+/// duration, optionally in the provided timezone (timestamps only).
+pub fn get_seconds(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    match target {
+        CelType::Duration(d) => Ok(CelType::Int(d.num_seconds() % 60).into()),
+        _ => {
+            let dt = apply_timezone("getSeconds", as_timestamp("getSeconds", target)?, args, ctx)?;
+            Ok(CelType::Int(dt.second() as i64).into())
         }
     }
+}
 
-    #[test]
-    fn test_map() {
-        let tests = vec![
-            ("map list", "[1, 2, 3].map(x, x * 2) == [2, 4, 6]"),
-            ("map list 2", "[1, 2, 3].map(y, y + 1) == [2, 3, 4]"),
+/// Returns the millisecond component of the target timestamp or duration,
+/// optionally in the provided timezone (timestamps only).
+pub fn get_milliseconds(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    match target {
+        CelType::Duration(d) => Ok(CelType::Int(d.num_milliseconds() % 1_000).into()),
+        _ => {
+            let dt = apply_timezone(
+                "getMilliseconds",
+                as_timestamp("getMilliseconds", target)?,
+                args,
+                ctx,
+            )?;
+            Ok(CelType::Int(dt.timestamp_subsec_millis() as i64).into())
+        }
+    }
+}
+
+/// Converts the provided argument to a [`CelType::Int`]. Supports
+/// conversion from `double` (truncating towards zero), `string` (parsing
+/// decimal digits), `uint`, and `timestamp` (as Unix seconds). Errors on
+/// overflow or an unsupported source type. Cannot be called as a method.
+///
+/// # Examples
+/// ```cel
+/// int(3.9) == 3
+/// int('42') == 42
+/// ```
+pub fn int(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    if target.is_some() {
+        return Err(ExecutionError::not_supported_as_method(
+            "int",
+            target.cloned().unwrap(),
+        ));
+    }
+    let value = resolve_arg("int", args, 0, ctx)?;
+    let result = match value {
+        CelType::Int(i) => i,
+        CelType::UInt(u) => u
+            .try_into()
+            .map_err(|_| ExecutionError::function_error("int", "uint value overflows int"))?,
+        CelType::Double(d) => {
+            // `i64::MAX as f64` rounds up to 2^63, one past the largest
+            // representable i64, so the upper bound must be checked with
+            // `>=` rather than `>` or the cast below silently saturates.
+            let truncated = d.trunc();
+            if truncated < i64::MIN as f64 || truncated >= i64::MAX as f64 {
+                return Err(ExecutionError::function_error("int", &format!("double {} overflows int", d)));
+            }
+            truncated as i64
+        }
+        CelType::String(s) => s
+            .parse()
+            .map_err(|_| ExecutionError::function_error("int", &format!("cannot parse '{}' as int", s)))?,
+        CelType::Timestamp(dt) => dt
+            .timestamp()
+            .try_into()
+            .map_err(|_| ExecutionError::function_error("int", "timestamp seconds overflow int"))?,
+        other => {
+            return Err(ExecutionError::function_error(
+                "int",
+                &format!("cannot convert {:?} to int", other),
+            ))
+        }
+    };
+    Ok(CelType::Int(result).into())
+}
+
+/// Converts the provided argument to a [`CelType::UInt`]. Supports
+/// conversion from non-negative `int`, `double` (truncating towards zero),
+/// and `string` (parsing decimal digits). Errors on overflow, a negative
+/// source value, or an unsupported source type. Cannot be called as a
+/// method.
+///
+/// # Examples
+/// ```cel
+/// uint(42) == 42u
+/// ```
+pub fn uint(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    if target.is_some() {
+        return Err(ExecutionError::not_supported_as_method(
+            "uint",
+            target.cloned().unwrap(),
+        ));
+    }
+    let value = resolve_arg("uint", args, 0, ctx)?;
+    let result = match value {
+        CelType::UInt(u) => u,
+        CelType::Int(i) => i
+            .try_into()
+            .map_err(|_| ExecutionError::function_error("uint", "int value is negative"))?,
+        CelType::Double(d) => {
+            // Same rationale as `int`'s Double conversion: `u64::MAX as
+            // f64` rounds up to 2^64, one past the largest representable
+            // u64, so the upper bound must be checked with `>=` after
+            // truncating or the cast below silently saturates.
+            let truncated = d.trunc();
+            if truncated < 0.0 {
+                return Err(ExecutionError::function_error(
+                    "uint",
+                    &format!("cannot convert negative double {} to uint", d),
+                ));
+            }
+            if truncated >= u64::MAX as f64 {
+                return Err(ExecutionError::function_error(
+                    "uint",
+                    &format!("double {} overflows uint", d),
+                ));
+            }
+            truncated as u64
+        }
+        CelType::String(s) => s
+            .parse()
+            .map_err(|_| ExecutionError::function_error("uint", &format!("cannot parse '{}' as uint", s)))?,
+        other => {
+            return Err(ExecutionError::function_error(
+                "uint",
+                &format!("cannot convert {:?} to uint", other),
+            ))
+        }
+    };
+    Ok(CelType::UInt(result).into())
+}
+
+/// Converts the provided argument to a [`CelType::Double`]. Supports
+/// conversion from `int`, `uint`, and `string`. Cannot be called as a
+/// method.
+///
+/// # Examples
+/// ```cel
+/// double('3.5') == 3.5
+/// ```
+pub fn double(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    if target.is_some() {
+        return Err(ExecutionError::not_supported_as_method(
+            "double",
+            target.cloned().unwrap(),
+        ));
+    }
+    let value = resolve_arg("double", args, 0, ctx)?;
+    let result = match value {
+        CelType::Double(d) => d,
+        CelType::Int(i) => i as f64,
+        CelType::UInt(u) => u as f64,
+        CelType::String(s) => s.parse().map_err(|_| {
+            ExecutionError::function_error("double", &format!("cannot parse '{}' as double", s))
+        })?,
+        other => {
+            return Err(ExecutionError::function_error(
+                "double",
+                &format!("cannot convert {:?} to double", other),
+            ))
+        }
+    };
+    Ok(CelType::Double(result).into())
+}
+
+/// Converts the provided argument to a [`CelType::String`]. Supports
+/// conversion from `bytes` (as UTF-8), `timestamp` (as RFC 3339), `int`,
+/// `uint`, `double`, and `bool`. Cannot be called as a method.
+///
+/// # Examples
+/// ```cel
+/// string(42) == '42'
+/// ```
+pub fn string(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    if target.is_some() {
+        return Err(ExecutionError::not_supported_as_method(
+            "string",
+            target.cloned().unwrap(),
+        ));
+    }
+    let value = resolve_arg("string", args, 0, ctx)?;
+    let result = match value {
+        CelType::String(s) => (*s).clone(),
+        CelType::Bytes(b) => String::from_utf8((*b).clone())
+            .map_err(|_| ExecutionError::function_error("string", "bytes are not valid UTF-8"))?,
+        CelType::Timestamp(dt) => dt.to_rfc3339(),
+        CelType::Int(i) => i.to_string(),
+        CelType::UInt(u) => u.to_string(),
+        CelType::Double(d) => d.to_string(),
+        CelType::Bool(b) => b.to_string(),
+        other => {
+            return Err(ExecutionError::function_error(
+                "string",
+                &format!("cannot convert {:?} to string", other),
+            ))
+        }
+    };
+    Ok(CelType::String(Rc::new(result)).into())
+}
+
+/// Converts the provided argument to [`CelType::Bytes`]. Supports
+/// conversion from `string` (its UTF-8 encoding). Cannot be called as a
+/// method.
+///
+/// # Examples
+/// ```cel
+/// bytes('abc') == b'abc'
+/// ```
+pub fn bytes(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    if target.is_some() {
+        return Err(ExecutionError::not_supported_as_method(
+            "bytes",
+            target.cloned().unwrap(),
+        ));
+    }
+    let value = resolve_arg("bytes", args, 0, ctx)?;
+    match value {
+        CelType::String(s) => Ok(CelType::Bytes(Rc::new(s.as_bytes().to_vec())).into()),
+        other => Err(ExecutionError::function_error(
+            "bytes",
+            &format!("cannot convert {:?} to bytes", other),
+        )),
+    }
+}
+
+/// Converts the provided argument to a [`CelType::Bool`]. Supports
+/// conversion from the strings `"true"` and `"false"`. Cannot be called as
+/// a method.
+///
+/// # Examples
+/// ```cel
+/// bool('true') == true
+/// ```
+pub fn bool(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    if target.is_some() {
+        return Err(ExecutionError::not_supported_as_method(
+            "bool",
+            target.cloned().unwrap(),
+        ));
+    }
+    let value = resolve_arg("bool", args, 0, ctx)?;
+    let result = match value {
+        CelType::Bool(b) => b,
+        CelType::String(s) => match s.as_str() {
+            "true" => true,
+            "false" => false,
+            _ => {
+                return Err(ExecutionError::function_error(
+                    "bool",
+                    &format!("cannot parse '{}' as bool", s),
+                ))
+            }
+        },
+        other => {
+            return Err(ExecutionError::function_error(
+                "bool",
+                &format!("cannot convert {:?} to bool", other),
+            ))
+        }
+    };
+    Ok(CelType::Bool(result).into())
+}
+
+/// Returns the dynamic type of the provided argument as a name string, e.g.
+/// `"int"` or `"list"`. Implements the CEL `type()` conversion function.
+/// Cannot be called as a method.
+///
+/// # Examples
+/// ```cel
+/// type(42) == 'int'
+/// ```
+pub fn type_of(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    if target.is_some() {
+        return Err(ExecutionError::not_supported_as_method(
+            "type",
+            target.cloned().unwrap(),
+        ));
+    }
+    let value = resolve_arg("type", args, 0, ctx)?;
+    let name = match value {
+        CelType::Int(_) => "int",
+        CelType::UInt(_) => "uint",
+        CelType::Double(_) => "double",
+        CelType::String(_) => "string",
+        CelType::Bytes(_) => "bytes",
+        CelType::Bool(_) => "bool",
+        CelType::List(_) => "list",
+        CelType::Map(_) => "map",
+        CelType::Timestamp(_) => "timestamp",
+        CelType::Duration(_) => "duration",
+        CelType::Null => "null_type",
+    };
+    Ok(CelType::String(Rc::new(name.to_string())).into())
+}
+
+/// Returns true if the provided argument can be resolved. This function is
+/// useful for checking if a property exists on a type before attempting to
+/// resolve it. Resolving a property that does not exist will result in a
+/// [`ExecutionError::NoSuchKey`] error.
+///
+/// Operates similar to the `has` macro describe in the Go CEL implementation
+/// spec: https://github.com/google/cel-spec/blob/master/doc/langdef.md#macros.
+///
+/// # Examples
+/// ```cel
+/// has(foo.bar.baz)
+/// ```
+pub fn has(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    if target.is_some() {
+        return Err(ExecutionError::not_supported_as_method(
+            "has",
+            target.cloned().unwrap(),
+        ));
+    }
+    let arg = args
+        .get(0)
+        .ok_or(ExecutionError::invalid_argument_count(1, 0))?;
+
+    // We determine if a type has a property by attempting to resolve it.
+    // If we get a NoSuchKey error, then we know the property does not exist
+    match CelType::resolve(arg, ctx) {
+        Ok(_) => CelType::Bool(true),
+        Err(err) => match err {
+            ExecutionError::NoSuchKey(_) => CelType::Bool(false),
+            _ => return Err(err),
+        },
+    }
+    .into()
+}
+
+/// Maps the provided list or map to a new list by applying an expression to
+/// each input item. This function is intended to be used like the CEL-go
+/// `map` macro: https://github.com/google/cel-spec/blob/master/doc/langdef.md#macros
+///
+/// The macro allows the user to assign each item in the list to an arbitrary
+/// identifier, and then use that identifier in the expression. In order to
+/// make this work here, we clone the context which creates a [`Context::Child`]
+/// context with the new variable. The child context has it's own variable
+/// space, so you can think about this is a sort of scoping mechanism.
+///
+/// Iterating a [`CelType::Map`] binds the identifier to each *key*, matching
+/// CEL's map-comprehension semantics. The order of the resulting list follows
+/// the map's key iteration order, which is not defined.
+///
+/// # Examples
+/// ```cel
+/// [1, 2, 3].map(x, x * 2) == [2, 4, 6]
+/// {'a': 1}.map(k, k) == ['a']
+/// ```
+pub fn map(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    if args.len() != 2 {
+        return Err(ExecutionError::invalid_argument_count(2, args.len()));
+    }
+    let ident = get_ident("map", &args[0])?;
+    let items = iteration_items("map", target)?;
+    let mut values = Vec::with_capacity(items.len());
+
+    // Initialize a new context where we'll store our intermediate identifier
+    // for each item that we're mapping over. This ensures that we don't overwrite
+    // any identifiers in the parent scope just because we use the same name in
+    // the mapping expression.
+    let mut ctx = ctx.clone();
+    for item in items {
+        ctx.add_variable(&**ident, item);
+        let value = CelType::resolve(&args[1], &ctx)?;
+        values.push(value);
+    }
+
+    Ok(CelType::List(Rc::new(values)))
+}
+
+/// Filters the provided list or map down to a new list containing only the
+/// items for which the predicate expression evaluates to `true`. This
+/// function is intended to be used like the CEL-go `filter` macro: https://github.com/google/cel-spec/blob/master/doc/langdef.md#macros
+///
+/// Uses the same child-context scoping trick as [`map`]: each item is bound
+/// to the provided identifier in a cloned [`Context`] before the predicate
+/// is resolved. Iterating a [`CelType::Map`] binds the identifier to each
+/// key.
+///
+/// # Examples
+/// ```cel
+/// [1, 2, 3].filter(x, x > 1) == [2, 3]
+/// ```
+pub fn filter(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    if args.len() != 2 {
+        return Err(ExecutionError::invalid_argument_count(2, args.len()));
+    }
+    let ident = get_ident("filter", &args[0])?;
+    let items = iteration_items("filter", target)?;
+    let mut values = Vec::new();
+    let mut ctx = ctx.clone();
+    for item in items {
+        ctx.add_variable(&**ident, item.clone());
+        match CelType::resolve(&args[1], &ctx)? {
+            CelType::Bool(true) => values.push(item),
+            CelType::Bool(false) => {}
+            other => {
+                return Err(ExecutionError::function_error(
+                    "filter",
+                    &format!("predicate must return a bool, found {:?}", other),
+                ))
+            }
+        }
+    }
+
+    Ok(CelType::List(Rc::new(values)))
+}
+
+/// Returns `true` if every item in the target list or map satisfies the
+/// predicate expression, short-circuiting as soon as an item fails. This
+/// function is intended to be used like the CEL-go `all` macro: https://github.com/google/cel-spec/blob/master/doc/langdef.md#macros
+/// Iterating a [`CelType::Map`] binds the identifier to each key.
+///
+/// # Examples
+/// ```cel
+/// [1, 2, 3].all(x, x > 0) == true
+/// ```
+pub fn all(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    if args.len() != 2 {
+        return Err(ExecutionError::invalid_argument_count(2, args.len()));
+    }
+    let ident = get_ident("all", &args[0])?;
+    let items = iteration_items("all", target)?;
+    let mut ctx = ctx.clone();
+    for item in items {
+        ctx.add_variable(&**ident, item);
+        match CelType::resolve(&args[1], &ctx)? {
+            CelType::Bool(true) => {}
+            CelType::Bool(false) => return Ok(CelType::Bool(false)),
+            other => {
+                return Err(ExecutionError::function_error(
+                    "all",
+                    &format!("predicate must return a bool, found {:?}", other),
+                ))
+            }
+        }
+    }
+
+    Ok(CelType::Bool(true))
+}
+
+/// Returns `true` if at least one item in the target list or map satisfies
+/// the predicate expression, short-circuiting as soon as a match is found.
+/// This function is intended to be used like the CEL-go `exists` macro: https://github.com/google/cel-spec/blob/master/doc/langdef.md#macros
+/// Iterating a [`CelType::Map`] binds the identifier to each key.
+///
+/// # Examples
+/// ```cel
+/// [1, 2, 3].exists(x, x > 2) == true
+/// ```
+pub fn exists(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    if args.len() != 2 {
+        return Err(ExecutionError::invalid_argument_count(2, args.len()));
+    }
+    let ident = get_ident("exists", &args[0])?;
+    let items = iteration_items("exists", target)?;
+    let mut ctx = ctx.clone();
+    for item in items {
+        ctx.add_variable(&**ident, item);
+        match CelType::resolve(&args[1], &ctx)? {
+            CelType::Bool(true) => return Ok(CelType::Bool(true)),
+            CelType::Bool(false) => {}
+            other => {
+                return Err(ExecutionError::function_error(
+                    "exists",
+                    &format!("predicate must return a bool, found {:?}", other),
+                ))
+            }
+        }
+    }
+
+    Ok(CelType::Bool(false))
+}
+
+/// Returns `true` iff exactly one item in the target list or map satisfies
+/// the predicate expression. Unlike [`exists`], every item must be checked
+/// since a second match later in the iteration invalidates an earlier one.
+/// This function is intended to be used like the CEL-go `exists_one` macro: https://github.com/google/cel-spec/blob/master/doc/langdef.md#macros
+/// Iterating a [`CelType::Map`] binds the identifier to each key.
+///
+/// # Examples
+/// ```cel
+/// [1, 2, 3].exists_one(x, x == 2) == true
+/// ```
+pub fn exists_one(
+    target: Option<&CelType>,
+    args: &[Expression],
+    ctx: &Context,
+) -> Result<CelType, ExecutionError> {
+    let target = target.ok_or(ExecutionError::missing_argument_or_target())?;
+    if args.len() != 2 {
+        return Err(ExecutionError::invalid_argument_count(2, args.len()));
+    }
+    let ident = get_ident("exists_one", &args[0])?;
+    let items = iteration_items("exists_one", target)?;
+    let mut matches = 0;
+    let mut ctx = ctx.clone();
+    for item in items {
+        ctx.add_variable(&**ident, item);
+        match CelType::resolve(&args[1], &ctx)? {
+            CelType::Bool(true) => matches += 1,
+            CelType::Bool(false) => {}
+            other => {
+                return Err(ExecutionError::function_error(
+                    "exists_one",
+                    &format!("predicate must return a bool, found {:?}", other),
+                ))
+            }
+        }
+    }
+
+    Ok(CelType::Bool(matches == 1))
+}
+
+/// Returns the items to bind the comprehension identifier to while
+/// evaluating [`map`], [`filter`], [`all`], [`exists`], and [`exists_one`].
+/// A [`CelType::List`] yields its elements; a [`CelType::Map`] yields its
+/// keys, matching CEL's map-comprehension semantics.
+fn iteration_items(fn_name: &str, target: &CelType) -> Result<Vec<CelType>, ExecutionError> {
+    match target {
+        CelType::List(items) => Ok(items.as_ref().clone()),
+        CelType::Map(m) => Ok(m.map.keys().cloned().map(CelType::from).collect()),
+        _ => Err(ExecutionError::function_error(
+            fn_name,
+            &format!("{} can only be called on a list or map", fn_name),
+        )),
+    }
+}
+
+fn get_ident(name: &str, expr: &Expression) -> Result<Rc<String>, ExecutionError> {
+    match expr {
+        Expression::Ident(ident) => Ok(ident.clone()),
+        _ => Err(ExecutionError::function_error(
+            name,
+            "first argument must be an identifier",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Context;
+    use crate::testing::test_script;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_size() {
+        let tests = vec![
+            ("size of list", "size([1, 2, 3]) == 3"),
+            ("size of map", "size({'a': 1, 'b': 2, 'c': 3}) == 3"),
+            ("size of string", "size('foo') == 3"),
+            ("size of bytes", "size(b'foo') == 3"),
+        ];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_contains_bytes_multi_byte() {
+        let tests = vec![
+            ("single byte", "b'abc'.contains(b'c') == true"),
+            ("multi byte match", "b'abc'.contains(b'bc') == true"),
+            ("multi byte no match", "b'abc'.contains(b'cd') == false"),
+            ("empty needle", "b'abc'.contains(b'') == true"),
+        ];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_map_comprehensions_over_maps() {
+        let tests = vec![
+            ("filter keys", "{'a': 1, 'b': 2}.filter(k, k == 'a') == ['a']"),
+            ("all keys", "{'a': 1, 'b': 2}.all(k, k.startsWith('')) == true"),
+            ("exists keys", "{'a': 1, 'b': 2}.exists(k, k == 'b') == true"),
+            (
+                "exists_one keys",
+                "{'a': 1, 'b': 2}.exists_one(k, k == 'b') == true",
+            ),
+        ];
+
+        for (name, script) in tests {
+            let ctx = Context::default();
+            assert_eq!(test_script(script, Some(ctx)), Ok(true.into()), "{}", name);
+        }
+
+        // `map`'s output order follows the underlying HashMap's key
+        // iteration order, which isn't guaranteed, so assert membership
+        // rather than a literal Vec order.
+        let ctx = Context::default();
+        let result = test_script("{'a': 1, 'b': 2}.map(k, k + '!')", Some(ctx)).expect("map keys");
+        match result {
+            crate::objects::CelType::List(items) => {
+                assert_eq!(items.len(), 2, "map keys");
+                assert!(items.contains(&"a!".to_string().into()), "map keys");
+                assert!(items.contains(&"b!".to_string().into()), "map keys");
+            }
+            other => panic!("map keys: expected a list, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_custom_function() {
+        let mut ctx = Context::default();
+        ctx.add_function("isAdmin", |target, args, ctx| {
+            let name = match target {
+                Some(target) => target.clone(),
+                None => {
+                    let arg = args
+                        .get(0)
+                        .ok_or(crate::ExecutionError::invalid_argument_count(1, 0))?;
+                    crate::objects::CelType::resolve(arg, ctx)?
+                }
+            };
+            Ok(crate::objects::CelType::Bool(name == "root".to_string().into()))
+        });
+
+        let tests = vec![
+            ("as function", "isAdmin('root') == true"),
+            ("as function, false", "isAdmin('alice') == false"),
+            ("as method", "'root'.isAdmin() == true"),
+        ];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, Some(ctx.clone())), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let tests = vec![
+            ("matches true", "'hello world'.matches('^hello') == true"),
+            ("matches false", "'hello world'.matches('^world') == false"),
+        ];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_starts_ends_with() {
+        let tests = vec![
+            ("startsWith true", "'hello world'.startsWith('hello') == true"),
+            ("startsWith false", "'hello world'.startsWith('world') == false"),
+            ("endsWith true", "'hello world'.endsWith('world') == true"),
+            ("endsWith false", "'hello world'.endsWith('hello') == false"),
+        ];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_substring() {
+        let tests = vec![
+            ("substring", "'hello world'.substring(0, 5) == 'hello'"),
+            ("substring mid", "'hello world'.substring(6, 11) == 'world'"),
+        ];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_split() {
+        let tests = vec![("split", "'a,b,c'.split(',') == ['a', 'b', 'c']")];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_trim() {
+        let tests = vec![("trim", "'  hello  '.trim() == 'hello'")];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_index_of() {
+        let tests = vec![
+            ("indexOf found", "'hello world'.indexOf('world') == 6"),
+            ("indexOf not found", "'hello world'.indexOf('xyz') == -1"),
+        ];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_timestamp_accessors() {
+        let tests = vec![
+            (
+                "getFullYear",
+                "timestamp('2023-06-15T08:30:45.500Z').getFullYear() == 2023",
+            ),
+            (
+                "getMonth",
+                "timestamp('2023-06-15T08:30:45.500Z').getMonth() == 5",
+            ),
+            (
+                "getDayOfMonth",
+                "timestamp('2023-06-15T08:30:45.500Z').getDayOfMonth() == 14",
+            ),
+            (
+                "getHours",
+                "timestamp('2023-06-15T08:30:45.500Z').getHours() == 8",
+            ),
+            (
+                "getMinutes",
+                "timestamp('2023-06-15T08:30:45.500Z').getMinutes() == 30",
+            ),
+            (
+                "getSeconds",
+                "timestamp('2023-06-15T08:30:45.500Z').getSeconds() == 45",
+            ),
+            (
+                "getMilliseconds",
+                "timestamp('2023-06-15T08:30:45.500Z').getMilliseconds() == 500",
+            ),
+            (
+                "getHours with timezone",
+                "timestamp('2023-06-15T08:30:45Z').getHours('-01:00') == 7",
+            ),
+        ];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_duration_accessors() {
+        let tests = vec![
+            ("1h30m minutes", "duration('1h30m').getMinutes() == 30"),
+            ("300s seconds", "duration('300s').getSeconds() == 0"),
+        ];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_conversions() {
+        let tests = vec![
+            ("int from double", "int(3.9) == 3"),
+            ("int from string", "int('42') == 42"),
+            ("uint from int", "uint(42) == 42u"),
+            ("double from string", "double('3.5') == 3.5"),
+            ("string from int", "string(42) == '42'"),
+            ("string from bytes", "string(b'abc') == 'abc'"),
+            ("bytes from string", "bytes('abc') == b'abc'"),
+            ("bool from string true", "bool('true') == true"),
+            ("bool from string false", "bool('false') == false"),
+            ("type of int", "type(42) == 'int'"),
+            ("type of string", "type('x') == 'string'"),
+            ("type of list", "type([1, 2]) == 'list'"),
+        ];
+
+        for (name, script) in tests {
+            assert_eq!(test_script(script, None), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_has() {
+        let tests = vec![
+            ("map has", "has(foo.bar) == true"),
+            ("map has", "has(foo.bar) == true"),
+            ("map not has", "has(foo.baz) == false"),
+            ("map deep not has", "has(foo.baz.bar) == false"),
+        ];
+
+        for (name, script) in tests {
+            let mut ctx = Context::default();
+            ctx.add_variable("foo", HashMap::from([("bar", 1)]));
+            assert_eq!(test_script(script, Some(ctx)), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        let tests = vec![
+            ("map list", "[1, 2, 3].map(x, x * 2) == [2, 4, 6]"),
+            ("map list 2", "[1, 2, 3].map(y, y + 1) == [2, 3, 4]"),
+        ];
+
+        for (name, script) in tests {
+            let ctx = Context::default();
+            assert_eq!(test_script(script, Some(ctx)), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let tests = vec![
+            ("filter list", "[1, 2, 3].filter(x, x > 1) == [2, 3]"),
+            ("filter none match", "[1, 2, 3].filter(x, x > 5) == []"),
+        ];
+
+        for (name, script) in tests {
+            let ctx = Context::default();
+            assert_eq!(test_script(script, Some(ctx)), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_all() {
+        let tests = vec![
+            ("all true", "[1, 2, 3].all(x, x > 0) == true"),
+            ("all false", "[1, 2, 3].all(x, x > 1) == false"),
+        ];
+
+        for (name, script) in tests {
+            let ctx = Context::default();
+            assert_eq!(test_script(script, Some(ctx)), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_exists() {
+        let tests = vec![
+            ("exists true", "[1, 2, 3].exists(x, x > 2) == true"),
+            ("exists false", "[1, 2, 3].exists(x, x > 5) == false"),
+        ];
+
+        for (name, script) in tests {
+            let ctx = Context::default();
+            assert_eq!(test_script(script, Some(ctx)), Ok(true.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_exists_one() {
+        let tests = vec![
+            ("exists_one true", "[1, 2, 3].exists_one(x, x == 2) == true"),
+            (
+                "exists_one too many",
+                "[1, 2, 2].exists_one(x, x == 2) == false",
+            ),
+            (
+                "exists_one none",
+                "[1, 2, 3].exists_one(x, x == 5) == false",
+            ),
         ];
 
         for (name, script) in tests {
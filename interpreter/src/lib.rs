@@ -0,0 +1,11 @@
+mod context;
+mod errors;
+mod functions;
+mod objects;
+
+#[cfg(test)]
+mod testing;
+
+pub use context::Context;
+pub use errors::ExecutionError;
+pub use objects::CelType;
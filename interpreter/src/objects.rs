@@ -0,0 +1,411 @@
+use crate::context::Context;
+use crate::functions;
+use crate::ExecutionError;
+use cel_parser::{ArithmeticOp, Atom, Expression, Member, RelationOp, UnaryOp};
+use chrono::Duration as ChronoDuration;
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+/// The dynamic value produced by resolving a CEL expression.
+#[derive(Debug, Clone)]
+pub enum CelType {
+    Int(i64),
+    UInt(u64),
+    Double(f64),
+    String(Rc<String>),
+    Bytes(Rc<Vec<u8>>),
+    Bool(bool),
+    List(Rc<Vec<CelType>>),
+    Map(CelMap),
+    Timestamp(DateTime<Utc>),
+    Duration(ChronoDuration),
+    Null,
+}
+
+/// A CEL map value. Wrapped in its own type (rather than a bare `HashMap`)
+/// because only a subset of [`CelType`] variants are valid map keys — see
+/// [`MapKey`].
+#[derive(Debug, Clone, Default)]
+pub struct CelMap {
+    pub map: HashMap<MapKey, CelType>,
+}
+
+/// The subset of [`CelType`] variants that are hashable and therefore valid
+/// as map keys, per the CEL spec.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    Int(i64),
+    UInt(u64),
+    Bool(bool),
+    String(Rc<String>),
+}
+
+impl TryFrom<CelType> for MapKey {
+    /// The [`CelType`] that could not be used as a map key, so the caller
+    /// can report it via [`ExecutionError::UnsupportedKeyType`].
+    type Error = CelType;
+
+    fn try_from(value: CelType) -> Result<Self, Self::Error> {
+        match value {
+            CelType::Int(i) => Ok(MapKey::Int(i)),
+            CelType::UInt(u) => Ok(MapKey::UInt(u)),
+            CelType::Bool(b) => Ok(MapKey::Bool(b)),
+            CelType::String(s) => Ok(MapKey::String(s)),
+            other => Err(other),
+        }
+    }
+}
+
+impl From<MapKey> for CelType {
+    fn from(key: MapKey) -> Self {
+        match key {
+            MapKey::Int(i) => CelType::Int(i),
+            MapKey::UInt(u) => CelType::UInt(u),
+            MapKey::Bool(b) => CelType::Bool(b),
+            MapKey::String(s) => CelType::String(s),
+        }
+    }
+}
+
+impl From<bool> for CelType {
+    fn from(value: bool) -> Self {
+        CelType::Bool(value)
+    }
+}
+
+impl From<i64> for CelType {
+    fn from(value: i64) -> Self {
+        CelType::Int(value)
+    }
+}
+
+impl From<String> for CelType {
+    fn from(value: String) -> Self {
+        CelType::String(Rc::new(value))
+    }
+}
+
+impl<K: Into<String>, V: Into<CelType>> From<HashMap<K, V>> for CelType {
+    fn from(value: HashMap<K, V>) -> Self {
+        let map = value
+            .into_iter()
+            .map(|(k, v)| (MapKey::String(Rc::new(k.into())), v.into()))
+            .collect();
+        CelType::Map(CelMap { map })
+    }
+}
+
+/// Lets every built-in in [`crate::functions`] end with `value.into()` as a
+/// plain [`CelType`] rather than wrapping it in `Ok` at every return site.
+impl From<CelType> for Result<CelType, ExecutionError> {
+    fn from(value: CelType) -> Self {
+        Ok(value)
+    }
+}
+
+impl PartialEq for CelType {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CelType::Int(a), CelType::Int(b)) => a == b,
+            (CelType::UInt(a), CelType::UInt(b)) => a == b,
+            (CelType::Double(a), CelType::Double(b)) => a == b,
+            (CelType::String(a), CelType::String(b)) => a == b,
+            (CelType::Bytes(a), CelType::Bytes(b)) => a == b,
+            (CelType::Bool(a), CelType::Bool(b)) => a == b,
+            (CelType::List(a), CelType::List(b)) => a == b,
+            (CelType::Map(a), CelType::Map(b)) => a.map == b.map,
+            (CelType::Timestamp(a), CelType::Timestamp(b)) => a == b,
+            (CelType::Duration(a), CelType::Duration(b)) => a == b,
+            (CelType::Null, CelType::Null) => true,
+            // Mixed numeric comparisons are legal in CEL (`1 == 1u`).
+            (CelType::Int(a), CelType::UInt(b)) | (CelType::UInt(b), CelType::Int(a)) => {
+                *a >= 0 && *a as u64 == *b
+            }
+            _ => false,
+        }
+    }
+}
+
+impl PartialOrd for CelType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (CelType::Int(a), CelType::Int(b)) => a.partial_cmp(b),
+            (CelType::UInt(a), CelType::UInt(b)) => a.partial_cmp(b),
+            (CelType::Double(a), CelType::Double(b)) => a.partial_cmp(b),
+            (CelType::String(a), CelType::String(b)) => a.partial_cmp(b),
+            (CelType::Bytes(a), CelType::Bytes(b)) => a.partial_cmp(b),
+            (CelType::Bool(a), CelType::Bool(b)) => a.partial_cmp(b),
+            // Comparing two timestamps yields their chronological order;
+            // comparing two durations yields their relative length.
+            (CelType::Timestamp(a), CelType::Timestamp(b)) => a.partial_cmp(b),
+            (CelType::Duration(a), CelType::Duration(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+}
+
+impl CelType {
+    /// Resolves a parsed CEL expression to a value, threading `ctx` through
+    /// child scopes as macros like [`crate::functions::map`] introduce new
+    /// bindings.
+    pub fn resolve(expr: &Expression, ctx: &Context) -> Result<CelType, ExecutionError> {
+        match expr {
+            Expression::Atom(atom) => Ok(atom.clone().into()),
+            Expression::Ident(name) => ctx
+                .resolve_variable(name)
+                .cloned()
+                .ok_or_else(|| ExecutionError::no_such_key(name.clone())),
+            Expression::List(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| CelType::resolve(item, ctx))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(CelType::List(Rc::new(items)))
+            }
+            Expression::Map(entries) => {
+                let mut map = HashMap::with_capacity(entries.len());
+                for (key_expr, value_expr) in entries {
+                    let key = CelType::resolve(key_expr, ctx)?;
+                    let value = CelType::resolve(value_expr, ctx)?;
+                    let key = MapKey::try_from(key).map_err(ExecutionError::UnsupportedKeyType)?;
+                    map.insert(key, value);
+                }
+                Ok(CelType::Map(CelMap { map }))
+            }
+            Expression::Arithmetic(lhs, op, rhs) => {
+                let lhs = CelType::resolve(lhs, ctx)?;
+                let rhs = CelType::resolve(rhs, ctx)?;
+                match (lhs, op, rhs) {
+                    (CelType::Int(a), ArithmeticOp::Add, CelType::Int(b)) => a
+                        .checked_add(b)
+                        .map(CelType::Int)
+                        .ok_or_else(|| overflow_error("+", "int")),
+                    (CelType::Int(a), ArithmeticOp::Subtract, CelType::Int(b)) => a
+                        .checked_sub(b)
+                        .map(CelType::Int)
+                        .ok_or_else(|| overflow_error("-", "int")),
+                    (CelType::Int(a), ArithmeticOp::Multiply, CelType::Int(b)) => a
+                        .checked_mul(b)
+                        .map(CelType::Int)
+                        .ok_or_else(|| overflow_error("*", "int")),
+                    (CelType::Int(a), ArithmeticOp::Divide, CelType::Int(b)) if b != 0 => a
+                        .checked_div(b)
+                        .map(CelType::Int)
+                        .ok_or_else(|| overflow_error("/", "int")),
+                    (CelType::Int(a), ArithmeticOp::Modulus, CelType::Int(b)) if b != 0 => a
+                        .checked_rem(b)
+                        .map(CelType::Int)
+                        .ok_or_else(|| overflow_error("%", "int")),
+                    (CelType::UInt(a), ArithmeticOp::Add, CelType::UInt(b)) => a
+                        .checked_add(b)
+                        .map(CelType::UInt)
+                        .ok_or_else(|| overflow_error("+", "uint")),
+                    (CelType::UInt(a), ArithmeticOp::Subtract, CelType::UInt(b)) => a
+                        .checked_sub(b)
+                        .map(CelType::UInt)
+                        .ok_or_else(|| overflow_error("-", "uint")),
+                    (CelType::UInt(a), ArithmeticOp::Multiply, CelType::UInt(b)) => a
+                        .checked_mul(b)
+                        .map(CelType::UInt)
+                        .ok_or_else(|| overflow_error("*", "uint")),
+                    (CelType::UInt(a), ArithmeticOp::Divide, CelType::UInt(b)) if b != 0 => a
+                        .checked_div(b)
+                        .map(CelType::UInt)
+                        .ok_or_else(|| overflow_error("/", "uint")),
+                    (CelType::UInt(a), ArithmeticOp::Modulus, CelType::UInt(b)) if b != 0 => a
+                        .checked_rem(b)
+                        .map(CelType::UInt)
+                        .ok_or_else(|| overflow_error("%", "uint")),
+                    (CelType::Double(a), ArithmeticOp::Add, CelType::Double(b)) => {
+                        Ok(CelType::Double(a + b))
+                    }
+                    (CelType::Double(a), ArithmeticOp::Subtract, CelType::Double(b)) => {
+                        Ok(CelType::Double(a - b))
+                    }
+                    (CelType::Double(a), ArithmeticOp::Multiply, CelType::Double(b)) => {
+                        Ok(CelType::Double(a * b))
+                    }
+                    (CelType::Double(a), ArithmeticOp::Divide, CelType::Double(b)) => {
+                        Ok(CelType::Double(a / b))
+                    }
+                    (CelType::String(a), ArithmeticOp::Add, CelType::String(b)) => {
+                        Ok(CelType::String(Rc::new(format!("{}{}", a, b))))
+                    }
+                    (CelType::Bytes(a), ArithmeticOp::Add, CelType::Bytes(b)) => {
+                        let mut bytes = (*a).clone();
+                        bytes.extend_from_slice(&b);
+                        Ok(CelType::Bytes(Rc::new(bytes)))
+                    }
+                    (CelType::List(a), ArithmeticOp::Add, CelType::List(b)) => {
+                        let mut items = (*a).clone();
+                        items.extend((*b).iter().cloned());
+                        Ok(CelType::List(Rc::new(items)))
+                    }
+                    // timestamp - timestamp -> duration
+                    (CelType::Timestamp(a), ArithmeticOp::Subtract, CelType::Timestamp(b)) => {
+                        Ok(CelType::Duration(a - b))
+                    }
+                    // timestamp +/- duration -> timestamp
+                    (CelType::Timestamp(a), ArithmeticOp::Add, CelType::Duration(b))
+                    | (CelType::Duration(b), ArithmeticOp::Add, CelType::Timestamp(a)) => {
+                        Ok(CelType::Timestamp(a + b))
+                    }
+                    (CelType::Timestamp(a), ArithmeticOp::Subtract, CelType::Duration(b)) => {
+                        Ok(CelType::Timestamp(a - b))
+                    }
+                    (CelType::Duration(a), ArithmeticOp::Add, CelType::Duration(b)) => {
+                        Ok(CelType::Duration(a + b))
+                    }
+                    (CelType::Duration(a), ArithmeticOp::Subtract, CelType::Duration(b)) => {
+                        Ok(CelType::Duration(a - b))
+                    }
+                    (a, op, b) => Err(ExecutionError::function_error(
+                        "arithmetic",
+                        &format!("unsupported operands for {:?}: {:?}, {:?}", op, a, b),
+                    )),
+                }
+            }
+            Expression::Relation(lhs, op, rhs) => {
+                let lhs = CelType::resolve(lhs, ctx)?;
+                let rhs = CelType::resolve(rhs, ctx)?;
+                let result = match op {
+                    RelationOp::Equals => lhs == rhs,
+                    RelationOp::NotEquals => lhs != rhs,
+                    RelationOp::LessThan => lhs < rhs,
+                    RelationOp::LessThanEq => lhs <= rhs,
+                    RelationOp::GreaterThan => lhs > rhs,
+                    RelationOp::GreaterThanEq => lhs >= rhs,
+                    RelationOp::In => match rhs {
+                        CelType::List(items) => items.contains(&lhs),
+                        CelType::Map(m) => MapKey::try_from(lhs)
+                            .map(|key| m.map.contains_key(&key))
+                            .unwrap_or(false),
+                        _ => false,
+                    },
+                };
+                Ok(CelType::Bool(result))
+            }
+            Expression::And(lhs, rhs) => Ok(CelType::Bool(
+                is_truthy(&CelType::resolve(lhs, ctx)?) && is_truthy(&CelType::resolve(rhs, ctx)?),
+            )),
+            Expression::Or(lhs, rhs) => Ok(CelType::Bool(
+                is_truthy(&CelType::resolve(lhs, ctx)?) || is_truthy(&CelType::resolve(rhs, ctx)?),
+            )),
+            Expression::Ternary(cond, when_true, when_false) => {
+                if is_truthy(&CelType::resolve(cond, ctx)?) {
+                    CelType::resolve(when_true, ctx)
+                } else {
+                    CelType::resolve(when_false, ctx)
+                }
+            }
+            Expression::Unary(op, expr) => {
+                let value = CelType::resolve(expr, ctx)?;
+                match (op, value) {
+                    (UnaryOp::Not, CelType::Bool(b)) | (UnaryOp::DoubleNot, CelType::Bool(b)) => {
+                        Ok(CelType::Bool(!b))
+                    }
+                    (UnaryOp::Minus, CelType::Int(i)) | (UnaryOp::DoubleMinus, CelType::Int(i)) => {
+                        Ok(CelType::Int(-i))
+                    }
+                    (UnaryOp::Minus, CelType::Double(d))
+                    | (UnaryOp::DoubleMinus, CelType::Double(d)) => Ok(CelType::Double(-d)),
+                    (op, value) => Err(ExecutionError::function_error(
+                        "unary",
+                        &format!("unsupported operand for {:?}: {:?}", op, value),
+                    )),
+                }
+            }
+            Expression::Member(target, member) => match member.as_ref() {
+                Member::Attribute(name) => match CelType::resolve(target, ctx)? {
+                    CelType::Map(m) => m
+                        .map
+                        .get(&MapKey::String(name.clone()))
+                        .cloned()
+                        .ok_or_else(|| ExecutionError::no_such_key(name.clone())),
+                    other => Err(ExecutionError::function_error(
+                        "member access",
+                        &format!("{:?} has no attribute '{}'", other, name),
+                    )),
+                },
+                Member::Index(index) => {
+                    let target = CelType::resolve(target, ctx)?;
+                    let index = CelType::resolve(index, ctx)?;
+                    match target {
+                        CelType::List(items) => match index {
+                            CelType::Int(i) if i >= 0 && (i as usize) < items.len() => {
+                                Ok(items[i as usize].clone())
+                            }
+                            _ => Err(ExecutionError::function_error(
+                                "index",
+                                &format!("index {:?} out of range", index),
+                            )),
+                        },
+                        CelType::Map(m) => {
+                            let key = MapKey::try_from(index)
+                                .map_err(ExecutionError::UnsupportedKeyType)?;
+                            m.map.get(&key).cloned().ok_or_else(|| {
+                                ExecutionError::no_such_key(Rc::new(format!("{:?}", key)))
+                            })
+                        }
+                        other => Err(ExecutionError::function_error(
+                            "index",
+                            &format!("{:?} is not indexable", other),
+                        )),
+                    }
+                }
+                Member::FunctionCall(name, args) => {
+                    let target = CelType::resolve(target, ctx)?;
+                    functions::call_function(name, Some(&target), args, ctx)
+                }
+                Member::Fields(_) => Err(ExecutionError::function_error(
+                    "member access",
+                    "struct field initializers are not supported",
+                )),
+            },
+            Expression::FunctionCall(name_expr, target_expr, args) => {
+                let name = match name_expr.as_ref() {
+                    Expression::Ident(name) => name.clone(),
+                    other => {
+                        return Err(ExecutionError::function_error(
+                            "call",
+                            &format!("expected a function name, found {:?}", other),
+                        ))
+                    }
+                };
+                let target = target_expr
+                    .as_ref()
+                    .map(|expr| CelType::resolve(expr, ctx))
+                    .transpose()?;
+                functions::call_function(&name, target.as_ref(), args, ctx)
+            }
+        }
+    }
+}
+
+fn is_truthy(value: &CelType) -> bool {
+    matches!(value, CelType::Bool(true))
+}
+
+/// Builds the [`ExecutionError::function_error`] raised when a checked
+/// arithmetic operator (see [`CelType::resolve`]'s `Expression::Arithmetic`
+/// arm) overflows `ty`'s range.
+fn overflow_error(op: &str, ty: &str) -> ExecutionError {
+    ExecutionError::function_error(op, &format!("{} overflows {}", op, ty))
+}
+
+impl From<Atom> for CelType {
+    fn from(atom: Atom) -> Self {
+        match atom {
+            Atom::Int(i) => CelType::Int(i),
+            Atom::UInt(u) => CelType::UInt(u),
+            Atom::Float(f) => CelType::Double(f),
+            Atom::String(s) => CelType::String(s),
+            Atom::Bytes(b) => CelType::Bytes(b),
+            Atom::Bool(b) => CelType::Bool(b),
+            Atom::Null => CelType::Null,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use crate::context::Context;
+use crate::objects::CelType;
+use crate::ExecutionError;
+
+/// Parses and resolves `script` against `ctx` (or a fresh default [`Context`]
+/// if none is given). Shared by every module's `#[cfg(test)]` block so that
+/// tests can assert against CEL source directly instead of building an
+/// [`cel_parser::Expression`] tree by hand.
+pub fn test_script(script: &str, ctx: Option<Context>) -> Result<CelType, ExecutionError> {
+    let expr = cel_parser::parse(script)
+        .unwrap_or_else(|e| panic!("failed to parse '{}': {:?}", script, e));
+    let ctx = ctx.unwrap_or_default();
+    CelType::resolve(&expr, &ctx)
+}